@@ -0,0 +1,159 @@
+//! Persistent `.claude-sandbox/config.toml` with named profiles, and
+//! `.claude-sandbox/.env` loading.
+//!
+//! Settings are resolved in the same "unified settings" order used
+//! elsewhere in the tool: CLI flags override the selected profile, which
+//! overrides built-in defaults.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::network::NetworkMode;
+
+const CONFIG_FILE: &str = "config.toml";
+const ENV_FILE: &str = ".env";
+
+/// Parsed `.claude-sandbox/config.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default, rename = "profile")]
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// A named `[profile.<name>]` table overriding the built-in defaults.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Profile {
+    pub cpus: Option<u8>,
+    pub memory: Option<u8>,
+    pub network: Option<String>,
+    /// Extra `host:container` bind mounts beyond the project directory.
+    #[serde(default)]
+    pub mounts: Vec<String>,
+    /// Names of host environment variables to forward into the container.
+    #[serde(default)]
+    pub env: Vec<String>,
+}
+
+impl Profile {
+    /// Parses the profile's `network` string (if any) into a `NetworkMode`.
+    pub fn network_mode(&self) -> Result<Option<NetworkMode>> {
+        self.network
+            .as_deref()
+            .map(|s| NetworkMode::from_str(s, true).map_err(|e| anyhow!(e)))
+            .transpose()
+    }
+}
+
+/// Loads `<sandbox_dir>/config.toml`, or an empty `Config` if absent.
+pub fn load(sandbox_dir: &Path) -> Result<Config> {
+    let path = sandbox_dir.join(CONFIG_FILE);
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    toml::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Looks up the requested profile, or the empty default profile if `name` is `None`.
+pub fn resolve_profile(config: &Config, name: Option<&str>) -> Result<Profile> {
+    match name {
+        None => Ok(Profile::default()),
+        Some(name) => config
+            .profiles
+            .get(name)
+            .cloned()
+            .with_context(|| format!("unknown profile '{name}' (not found in config.toml)")),
+    }
+}
+
+/// Loads `<sandbox_dir>/.env` as `KEY=VALUE` pairs (dotenv-style), or an
+/// empty list if the file doesn't exist.
+pub fn load_dotenv(sandbox_dir: &Path) -> Result<Vec<(String, String)>> {
+    let path = sandbox_dir.join(ENV_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    let mut vars = Vec::new();
+    for (lineno, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .with_context(|| format!("{}:{}: expected KEY=VALUE", path.display(), lineno + 1))?;
+        let value = value.trim().trim_matches('"').trim_matches('\'');
+        vars.push((key.trim().to_string(), value.to_string()));
+    }
+    Ok(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_profile_returns_default_when_unset() {
+        let config = Config::default();
+        let profile = resolve_profile(&config, None).unwrap();
+        assert_eq!(profile.cpus, None);
+    }
+
+    #[test]
+    fn resolve_profile_errors_on_unknown_name() {
+        let config = Config::default();
+        assert!(resolve_profile(&config, Some("heavy")).is_err());
+    }
+
+    #[test]
+    fn load_parses_named_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[profile.heavy]\ncpus = 8\nmemory = 8\nmounts = [\"/data:/data\"]\nenv = [\"FOO\"]\n",
+        )
+        .unwrap();
+        let config = load(dir.path()).unwrap();
+        let profile = resolve_profile(&config, Some("heavy")).unwrap();
+        assert_eq!(profile.cpus, Some(8));
+        assert_eq!(profile.memory, Some(8));
+        assert_eq!(profile.mounts, vec!["/data:/data".to_string()]);
+        assert_eq!(profile.env, vec!["FOO".to_string()]);
+    }
+
+    #[test]
+    fn load_dotenv_parses_key_value_pairs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(ENV_FILE),
+            "# comment\nFOO=bar\nBAZ=\"quoted\"\n\n",
+        )
+        .unwrap();
+        let vars = load_dotenv(dir.path()).unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("FOO".to_string(), "bar".to_string()),
+                ("BAZ".to_string(), "quoted".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn load_dotenv_returns_empty_when_absent() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_dotenv(dir.path()).unwrap().is_empty());
+    }
+}
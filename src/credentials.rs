@@ -0,0 +1,320 @@
+//! Credential providers for obtaining the Claude Code OAuth token.
+//!
+//! Mirrors how Cargo splits credential lookup into pluggable per-platform
+//! providers (macos-keychain, gnome-secret, wincred, ...): each provider
+//! knows how to fetch a token from exactly one source, and `auto` tries
+//! them in a defined order until one succeeds.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use log::debug;
+
+const KEYCHAIN_SERVICE: &str = "Claude Code-credentials";
+const OAUTH_TOKEN_ENV: &str = "CLAUDE_CODE_OAUTH_TOKEN";
+
+/// Selects which credential provider(s) `claude-sandbox` should use.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum CredentialSource {
+    /// Try providers in a fixed order, stopping at the first success.
+    Auto,
+    /// macOS Keychain via `security find-generic-password`.
+    Keychain,
+    /// The `CLAUDE_CODE_OAUTH_TOKEN` environment variable.
+    Env,
+    /// A JSON or plain-text token file on disk.
+    File,
+    /// Linux Secret Service (libsecret) via `secret-tool`.
+    SecretService,
+    /// An external command whose stdout is the token.
+    Command,
+}
+
+/// A source of the Claude Code OAuth token.
+pub trait CredentialProvider {
+    /// Human-readable name used in error messages.
+    fn name(&self) -> &str;
+
+    /// Fetch the token, or an error describing why this provider couldn't.
+    fn fetch_token(&self) -> Result<String>;
+}
+
+/// Reads the token from the macOS Keychain.
+pub struct KeychainProvider;
+
+impl CredentialProvider for KeychainProvider {
+    fn name(&self) -> &str {
+        "keychain"
+    }
+
+    fn fetch_token(&self) -> Result<String> {
+        debug!("reading keychain service: {}", KEYCHAIN_SERVICE);
+        let output = Command::new("security")
+            .args(["find-generic-password", "-s", KEYCHAIN_SERVICE, "-w"])
+            .output()
+            .context("failed to execute: security")?;
+
+        if !output.status.success() {
+            bail!("no entry found for service '{}'", KEYCHAIN_SERVICE);
+        }
+
+        let json_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if json_str.is_empty() {
+            bail!("keychain entry for '{}' was empty", KEYCHAIN_SERVICE);
+        }
+
+        let creds: serde_json::Value =
+            serde_json::from_str(&json_str).context("failed to parse keychain entry as JSON")?;
+
+        creds["claudeAiOauth"]["accessToken"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .context("no accessToken found in keychain credentials")
+    }
+}
+
+/// Reads the token from the `CLAUDE_CODE_OAUTH_TOKEN` environment variable.
+pub struct EnvProvider;
+
+impl CredentialProvider for EnvProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn fetch_token(&self) -> Result<String> {
+        env::var(OAUTH_TOKEN_ENV)
+            .ok()
+            .filter(|s| !s.is_empty())
+            .with_context(|| format!("{} is not set", OAUTH_TOKEN_ENV))
+    }
+}
+
+/// Reads the token from a JSON (`claudeAiOauth.accessToken`) or plain-text file.
+pub struct FileProvider {
+    pub path: PathBuf,
+}
+
+impl CredentialProvider for FileProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn fetch_token(&self) -> Result<String> {
+        let contents = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read {}", self.path.display()))?;
+        let trimmed = contents.trim();
+
+        if let Ok(creds) = serde_json::from_str::<serde_json::Value>(trimmed) {
+            if let Some(token) = creds["claudeAiOauth"]["accessToken"].as_str() {
+                if !token.is_empty() {
+                    return Ok(token.to_string());
+                }
+            }
+        }
+
+        if trimmed.is_empty() {
+            bail!("{} is empty", self.path.display());
+        }
+        Ok(trimmed.to_string())
+    }
+}
+
+/// Reads the token from the Linux Secret Service (libsecret) via `secret-tool`.
+pub struct SecretServiceProvider;
+
+impl CredentialProvider for SecretServiceProvider {
+    fn name(&self) -> &str {
+        "secret-service"
+    }
+
+    fn fetch_token(&self) -> Result<String> {
+        let output = Command::new("secret-tool")
+            .args(["lookup", "service", KEYCHAIN_SERVICE])
+            .output()
+            .context("failed to execute: secret-tool (is libsecret-tools installed?)")?;
+
+        if !output.status.success() {
+            bail!("no entry found for service '{}'", KEYCHAIN_SERVICE);
+        }
+
+        let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if token.is_empty() {
+            bail!("secret-tool returned an empty value");
+        }
+        Ok(token)
+    }
+}
+
+/// Shells out to an external command and uses its trimmed stdout as the token.
+pub struct CommandProvider {
+    pub command: String,
+}
+
+impl CredentialProvider for CommandProvider {
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    fn fetch_token(&self) -> Result<String> {
+        let output = Command::new("sh")
+            .args(["-c", &self.command])
+            .output()
+            .with_context(|| format!("failed to execute credential command: {}", self.command))?;
+
+        if !output.status.success() {
+            bail!(
+                "credential command '{}' exited with {}",
+                self.command,
+                output.status
+            );
+        }
+
+        let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if token.is_empty() {
+            bail!("credential command '{}' produced no output", self.command);
+        }
+        Ok(token)
+    }
+}
+
+/// Resolves the OAuth token according to `--credential-source`.
+///
+/// In `auto` mode, providers are tried in order (env, platform secret
+/// store, file, command), and all failures are aggregated into a single
+/// error if none succeed.
+pub fn fetch_token(
+    source: CredentialSource,
+    credential_file: Option<&Path>,
+    credential_command: Option<&str>,
+) -> Result<String> {
+    match source {
+        CredentialSource::Keychain => KeychainProvider.fetch_token(),
+        CredentialSource::Env => EnvProvider.fetch_token(),
+        CredentialSource::SecretService => SecretServiceProvider.fetch_token(),
+        CredentialSource::File => {
+            let path = credential_file
+                .context("--credential-file is required with --credential-source=file")?;
+            FileProvider {
+                path: path.to_path_buf(),
+            }
+            .fetch_token()
+        }
+        CredentialSource::Command => {
+            let command = credential_command
+                .context("--credential-command is required with --credential-source=command")?;
+            CommandProvider {
+                command: command.to_string(),
+            }
+            .fetch_token()
+        }
+        CredentialSource::Auto => fetch_token_auto(credential_file, credential_command),
+    }
+}
+
+fn fetch_token_auto(credential_file: Option<&Path>, credential_command: Option<&str>) -> Result<String> {
+    let mut providers: Vec<Box<dyn CredentialProvider>> = vec![Box::new(EnvProvider)];
+
+    if cfg!(target_os = "macos") {
+        providers.push(Box::new(KeychainProvider));
+    }
+    if cfg!(target_os = "linux") {
+        providers.push(Box::new(SecretServiceProvider));
+    }
+    if let Some(path) = credential_file {
+        providers.push(Box::new(FileProvider {
+            path: path.to_path_buf(),
+        }));
+    }
+    if let Some(command) = credential_command {
+        providers.push(Box::new(CommandProvider {
+            command: command.to_string(),
+        }));
+    }
+
+    try_providers(&providers)
+}
+
+fn try_providers(providers: &[Box<dyn CredentialProvider>]) -> Result<String> {
+    let mut errors = Vec::new();
+
+    for provider in providers {
+        debug!("trying credential provider: {}", provider.name());
+        match provider.fetch_token() {
+            Ok(token) => return Ok(token),
+            Err(err) => errors.push(format!("{}: {}", provider.name(), err)),
+        }
+    }
+
+    bail!(
+        "No OAuth token found via any credential provider:\n  {}\n\n\
+         Please authenticate using the official Claude CLI first:\n  \
+         claude auth login",
+        errors.join("\n  ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingProvider;
+
+    impl CredentialProvider for FailingProvider {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn fetch_token(&self) -> Result<String> {
+            bail!("always fails")
+        }
+    }
+
+    struct SucceedingProvider;
+
+    impl CredentialProvider for SucceedingProvider {
+        fn name(&self) -> &str {
+            "succeeding"
+        }
+
+        fn fetch_token(&self) -> Result<String> {
+            Ok("token-123".to_string())
+        }
+    }
+
+    #[test]
+    fn try_providers_returns_first_success() {
+        let providers: Vec<Box<dyn CredentialProvider>> =
+            vec![Box::new(FailingProvider), Box::new(SucceedingProvider)];
+        assert_eq!(try_providers(&providers).unwrap(), "token-123");
+    }
+
+    #[test]
+    fn try_providers_aggregates_errors_when_all_fail() {
+        let providers: Vec<Box<dyn CredentialProvider>> =
+            vec![Box::new(FailingProvider), Box::new(FailingProvider)];
+        let err = try_providers(&providers).unwrap_err().to_string();
+        assert!(err.contains("failing: always fails"));
+    }
+
+    #[test]
+    fn file_provider_reads_json_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("creds.json");
+        std::fs::write(&path, r#"{"claudeAiOauth":{"accessToken":"abc"}}"#).unwrap();
+        let provider = FileProvider { path };
+        assert_eq!(provider.fetch_token().unwrap(), "abc");
+    }
+
+    #[test]
+    fn file_provider_reads_plain_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token.txt");
+        std::fs::write(&path, "plain-token\n").unwrap();
+        let provider = FileProvider { path };
+        assert_eq!(provider.fetch_token().unwrap(), "plain-token");
+    }
+}
@@ -0,0 +1,181 @@
+//! Resolving the container image to run: a local build or a pulled
+//! registry image.
+//!
+//! Mirrors rustwide's `SandboxImage::local`/`::remote` split: a `Local`
+//! image must already exist (built via `claude-sandbox build`) and is
+//! run by name, since locally built images generally have no registry
+//! content digest. A `Remote` image is pulled from a registry and pinned
+//! to its `name@sha256:...` digest in `<sandbox_dir>/image.lock`, so a
+//! later run against the same reference reuses that exact digest instead
+//! of whatever `:latest` happens to point to by then.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+
+const IMAGE_LOCK_FILE: &str = "image.lock";
+
+/// A container image to run, either built locally or pulled from a registry.
+pub enum SandboxImage {
+    /// An image expected to already exist locally (built via `container build`).
+    Local(String),
+    /// An image reference to pull from a registry (e.g. `ghcr.io/org/image:tag`).
+    Remote(String),
+}
+
+/// The pinned reference recorded in `<sandbox_dir>/image.lock`.
+#[derive(Debug, Serialize, Deserialize)]
+struct ImageLock {
+    /// The `--image` reference this pin was resolved from.
+    reference: String,
+    /// The resolved `name@sha256:...` digest reference.
+    digest: String,
+}
+
+impl SandboxImage {
+    pub fn local(name: impl Into<String>) -> Self {
+        Self::Local(name.into())
+    }
+
+    pub fn remote(reference: impl Into<String>) -> Self {
+        Self::Remote(reference.into())
+    }
+
+    /// Resolves this image to the reference `container run` should use:
+    /// the local image's name, or a pulled remote image's pinned digest.
+    pub fn resolve(&self) -> Result<String> {
+        match self {
+            SandboxImage::Local(name) => {
+                if !image_exists(name)? {
+                    bail!(
+                        "Local image '{name}' not found.\n\
+                         Run 'claude-sandbox build' first to build it."
+                    );
+                }
+                Ok(name.clone())
+            }
+            SandboxImage::Remote(reference) => {
+                pull(reference)?;
+                digest_for(reference)
+            }
+        }
+    }
+
+    /// Resolves this image, reusing and persisting a pinned digest in
+    /// `<sandbox_dir>/image.lock` for `Remote` images so that repeated
+    /// runs against the same reference are reproducible. `Local` images
+    /// aren't pinned: they're expected to change whenever `build` is run.
+    pub fn resolve_and_pin(&self, sandbox_dir: &Path) -> Result<String> {
+        let reference = match self {
+            SandboxImage::Local(_) => return self.resolve(),
+            SandboxImage::Remote(reference) => reference,
+        };
+
+        if let Some(lock) = read_lock(sandbox_dir)? {
+            if &lock.reference == reference {
+                debug!(
+                    "reusing pinned digest for '{reference}' from {IMAGE_LOCK_FILE}: {}",
+                    lock.digest
+                );
+                return Ok(lock.digest);
+            }
+        }
+
+        let digest_ref = self.resolve()?;
+        write_lock(sandbox_dir, reference, &digest_ref)?;
+        Ok(digest_ref)
+    }
+
+    /// Forces a fresh `container pull` and overwrites `image.lock` with
+    /// the result, ignoring any existing pin. Used by `claude-sandbox
+    /// pull`, where an explicit pull must be able to move a tag forward;
+    /// `resolve_and_pin` is for `run`, where reusing an existing pin is
+    /// the point.
+    pub fn pull_and_pin(&self, sandbox_dir: &Path) -> Result<String> {
+        let reference = match self {
+            SandboxImage::Local(_) => return self.resolve(),
+            SandboxImage::Remote(reference) => reference,
+        };
+
+        let digest_ref = self.resolve()?;
+        write_lock(sandbox_dir, reference, &digest_ref)?;
+        Ok(digest_ref)
+    }
+}
+
+/// Pulls `reference` from its registry via `container pull`.
+pub fn pull(reference: &str) -> Result<String> {
+    debug!("pulling image: {}", reference);
+    let status = Command::new("container")
+        .args(["pull", reference])
+        .status()
+        .context("failed to execute: container pull")?;
+
+    if !status.success() {
+        bail!("container pull failed for '{reference}'");
+    }
+
+    let digest_ref = digest_for(reference)?;
+    info!("Pulled '{reference}' -> {digest_ref}");
+    Ok(digest_ref)
+}
+
+fn image_exists(name: &str) -> Result<bool> {
+    let status = Command::new("container")
+        .args(["image", "inspect", name])
+        .output()
+        .context("failed to execute: container image inspect")?;
+    Ok(status.status.success())
+}
+
+fn digest_for(reference: &str) -> Result<String> {
+    let output = Command::new("container")
+        .args(["image", "inspect", reference])
+        .output()
+        .context("failed to execute: container image inspect")?;
+
+    if !output.status.success() {
+        bail!("container image inspect failed for '{reference}'");
+    }
+
+    let inspected: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .context("failed to parse 'container image inspect' output as JSON")?;
+
+    let entry = inspected
+        .as_array()
+        .and_then(|arr| arr.first())
+        .unwrap_or(&inspected);
+
+    let digest = entry["Digest"]
+        .as_str()
+        .with_context(|| format!("no Digest field in inspect output for '{reference}'"))?;
+
+    let repo = reference.split('@').next().unwrap_or(reference);
+    Ok(format!("{repo}@{digest}"))
+}
+
+fn read_lock(sandbox_dir: &Path) -> Result<Option<ImageLock>> {
+    let path = sandbox_dir.join(IMAGE_LOCK_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    toml::from_str(&contents)
+        .map(Some)
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn write_lock(sandbox_dir: &Path, reference: &str, digest: &str) -> Result<()> {
+    let lock = ImageLock {
+        reference: reference.to_string(),
+        digest: digest.to_string(),
+    };
+    let contents = toml::to_string_pretty(&lock).context("failed to serialize image.lock")?;
+    fs::write(sandbox_dir.join(IMAGE_LOCK_FILE), contents)
+        .with_context(|| format!("failed to write {IMAGE_LOCK_FILE}"))
+}
@@ -3,23 +3,39 @@
 //! External commands used:
 //! - container --version
 //! - container build -t <image> -f <containerfile> <context>
-//! - container run --rm -it -e <env> -m <memory> -c <cpus> -v <volume> <image>
+//! - container pull <image>
+//! - container image inspect <image>
+//! - container run --rm -it -e <env> -m <memory> -c <cpus> -v <volume> [-v <mount>]... [--network none] [-e <name>]... <image>
 //! - security find-generic-password -s <service> -w
+//! - secret-tool lookup service <service>
+//! - kill -TERM -<pgid>|<pid> (to tear down a timed-out or interrupted run)
+
+mod config;
+mod credentials;
+mod image;
+mod network;
+mod process;
+mod proxy;
+mod ssh;
 
 use std::env;
 use std::fs;
 use std::io::Write;
-use std::os::unix::process::CommandExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
+use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
 use clap::Parser;
-use log::{debug, info};
+use log::{debug, info, warn};
+
+use credentials::CredentialSource;
+use image::SandboxImage;
+use network::NetworkMode;
+use process::{Io, LineAction, Timeouts};
 
 const SANDBOX_DIR: &str = ".claude-sandbox";
 const DEFAULT_IMAGE_NAME: &str = "claude-sandbox";
-const KEYCHAIN_SERVICE: &str = "Claude Code-credentials";
 
 #[derive(Parser)]
 #[command(
@@ -42,17 +58,72 @@ enum Commands {
     },
 
     /// Build container image from Containerfile in current directory
-    Build,
+    Build {
+        /// Maximum time to allow the build to run, e.g. "10m"
+        #[arg(long, value_parser = process::parse_duration)]
+        timeout: Option<Duration>,
+
+        /// Terminate the build if it produces no output for this long, e.g. "2m"
+        #[arg(long, value_parser = process::parse_duration)]
+        inactivity_timeout: Option<Duration>,
+    },
+
+    /// Pull a prebuilt image from a registry and pin it to its digest
+    Pull {
+        /// Image reference to pull, e.g. ghcr.io/org/claude-sandbox:latest
+        image: String,
+    },
 
     /// Run Claude Code in the container
     Run {
-        /// Number of CPUs (2-8)
-        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(2..=8))]
-        cpus: u8,
+        /// Number of CPUs (2-8) [default: 2, or the selected profile's value]
+        #[arg(long, value_parser = clap::value_parser!(u8).range(2..=8))]
+        cpus: Option<u8>,
+
+        /// Memory in GB (2-8) [default: 4, or the selected profile's value]
+        #[arg(long, value_parser = clap::value_parser!(u8).range(2..=8))]
+        memory: Option<u8>,
+
+        /// Named profile from .claude-sandbox/config.toml to use as a base
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Where to obtain the Claude Code OAuth token from
+        #[arg(long, value_enum, default_value = "auto")]
+        credential_source: CredentialSource,
+
+        /// Path to a token file, used with --credential-source=file
+        #[arg(long)]
+        credential_file: Option<PathBuf>,
+
+        /// Command whose stdout is the token, used with --credential-source=command
+        #[arg(long)]
+        credential_command: Option<String>,
+
+        /// Network access mode for the container: none, restricted, or full
+        /// [default: restricted, or the selected profile's value]
+        #[arg(long, value_enum)]
+        network: Option<NetworkMode>,
 
-        /// Memory in GB (2-8)
-        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(2..=8))]
-        memory: u8,
+        /// Pull and run this image reference instead of the local build
+        #[arg(long)]
+        image: Option<String>,
+
+        /// Bind-mount the host SSH agent socket into the container
+        #[arg(long)]
+        forward_ssh_agent: bool,
+
+        /// Mount ~/.gitconfig into the container read-only
+        #[arg(long)]
+        forward_gitconfig: bool,
+
+        /// Maximum time to allow the session to run, e.g. "2h"
+        #[arg(long, value_parser = process::parse_duration)]
+        timeout: Option<Duration>,
+
+        /// Terminate the session if it produces no output for this long, e.g. "10m"
+        #[arg(long, value_parser = process::parse_duration)]
+        inactivity_timeout: Option<Duration>,
     },
 }
 
@@ -63,8 +134,43 @@ fn main() -> Result<()> {
 
     match Cli::parse().command {
         Commands::Init { force } => cmd_init(force),
-        Commands::Build => cmd_build(),
-        Commands::Run { cpus, memory } => cmd_run(cpus, memory),
+        Commands::Build {
+            timeout,
+            inactivity_timeout,
+        } => cmd_build(Timeouts {
+            overall: timeout,
+            inactivity: inactivity_timeout,
+        }),
+        Commands::Pull { image } => cmd_pull(image),
+        Commands::Run {
+            cpus,
+            memory,
+            profile,
+            credential_source,
+            credential_file,
+            credential_command,
+            network,
+            image,
+            forward_ssh_agent,
+            forward_gitconfig,
+            timeout,
+            inactivity_timeout,
+        } => cmd_run(
+            cpus,
+            memory,
+            profile.as_deref(),
+            credential_source,
+            credential_file.as_deref(),
+            credential_command.as_deref(),
+            network,
+            image,
+            forward_ssh_agent,
+            forward_gitconfig,
+            Timeouts {
+                overall: timeout,
+                inactivity: inactivity_timeout,
+            },
+        ),
     }
 }
 
@@ -75,30 +181,52 @@ fn cmd_init(force: bool) -> Result<()> {
     init_sandbox(&sandbox_dir, force)
 }
 
-fn cmd_run(cpus: u8, memory: u8) -> Result<()> {
+fn cmd_pull(image: String) -> Result<()> {
     check_container_available()?;
-    debug!("reading keychain service: {}", KEYCHAIN_SERVICE);
-    let json_str = exec_output_quiet(
-        "security",
-        &["find-generic-password", "-s", KEYCHAIN_SERVICE, "-w"],
-    )
-    .filter(|o| o.status.success())
-    .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-    .filter(|s| !s.is_empty())
-    .context(
-        "No OAuth token found in keychain.\n\n\
-             Please authenticate using the official Claude CLI first:\n  \
-             claude auth login",
-    )?;
-
-    let creds: serde_json::Value =
-        serde_json::from_str(&json_str).context("Failed to parse keychain credentials as JSON")?;
-
-    let token = creds["claudeAiOauth"]["accessToken"]
-        .as_str()
-        .filter(|s| !s.is_empty())
-        .map(String::from)
-        .context("No accessToken found in keychain credentials")?;
+    let sandbox_dir = env::current_dir()
+        .context("failed to get current directory")?
+        .join(SANDBOX_DIR);
+    fs::create_dir_all(&sandbox_dir).context("failed to create .claude-sandbox directory")?;
+
+    let digest_ref = SandboxImage::remote(image).pull_and_pin(&sandbox_dir)?;
+    info!("Pinned image: {}", digest_ref);
+    Ok(())
+}
+
+fn cmd_run(
+    cpus: Option<u8>,
+    memory: Option<u8>,
+    profile_name: Option<&str>,
+    credential_source: CredentialSource,
+    credential_file: Option<&Path>,
+    credential_command: Option<&str>,
+    network: Option<NetworkMode>,
+    image: Option<String>,
+    forward_ssh_agent: bool,
+    forward_gitconfig: bool,
+    timeouts: Timeouts,
+) -> Result<()> {
+    check_container_available()?;
+    let token = credentials::fetch_token(credential_source, credential_file, credential_command)?;
+
+    let sandbox_dir = env::current_dir()
+        .context("failed to determine working directory")?
+        .join(SANDBOX_DIR);
+    fs::create_dir_all(&sandbox_dir).context("failed to create .claude-sandbox directory")?;
+
+    let config = config::load(&sandbox_dir)?;
+    let profile = config::resolve_profile(&config, profile_name)?;
+
+    let cpus = cpus.or(profile.cpus).unwrap_or(2);
+    let memory = memory.or(profile.memory).unwrap_or(4);
+    let network = network.or(profile.network_mode()?).unwrap_or_default();
+
+    if (forward_ssh_agent || forward_gitconfig) && matches!(network, NetworkMode::None) {
+        warn!(
+            "--forward-ssh-agent/--forward-gitconfig have no effect with --network none: \
+             the container has no outbound access to reach a remote git host"
+        );
+    }
 
     debug!("running with cpus={}, memory={}G", cpus, memory);
 
@@ -109,7 +237,13 @@ fn cmd_run(cpus: u8, memory: u8) -> Result<()> {
             .display()
     );
 
-    let args = vec![
+    let sandbox_image = match image {
+        Some(reference) => SandboxImage::remote(reference),
+        None => SandboxImage::local(DEFAULT_IMAGE_NAME),
+    };
+    let image_ref = sandbox_image.resolve_and_pin(&sandbox_dir)?;
+
+    let mut args = vec![
         "run".to_string(),
         "--rm".to_string(),
         "-it".to_string(),
@@ -121,20 +255,98 @@ fn cmd_run(cpus: u8, memory: u8) -> Result<()> {
         cpus.to_string(),
         "-v".to_string(),
         volume,
-        DEFAULT_IMAGE_NAME.to_string(),
     ];
+    for mount in &profile.mounts {
+        args.push("-v".to_string());
+        args.push(mount.clone());
+    }
+    args.extend(network::container_run_args(network));
+
+    let mut command_env = Vec::new();
+
+    // Keeps the proxy alive for the duration of the run; dropping it stops
+    // it from accepting further connections.
+    let mut _allowlist_proxy = None;
+    if matches!(network, NetworkMode::Restricted) {
+        let allowlist = network::load_allowlist(&sandbox_dir)?;
+        if allowlist.allow.is_empty() {
+            warn!(
+                "network mode is 'restricted' but .claude-sandbox/network.toml has no \
+                 'allow' entries; the allowlist proxy will refuse every connection"
+            );
+        }
+        let proxy = proxy::start(allowlist.allow).context("failed to start allowlist proxy")?;
+        let proxy_url = format!("http://host.containers.internal:{}", proxy.port);
+        for var in ["HTTP_PROXY", "HTTPS_PROXY"] {
+            args.push("-e".to_string());
+            args.push(var.to_string());
+            command_env.push((var.to_string(), proxy_url.clone()));
+        }
+        _allowlist_proxy = Some(proxy);
+    }
+
+    if forward_ssh_agent {
+        let sock = ssh::agent_socket_path()?;
+        args.push("-v".to_string());
+        args.push(format!("{0}:{0}", sock.display()));
+        args.push("-e".to_string());
+        args.push("SSH_AUTH_SOCK".to_string());
+        command_env.push(("SSH_AUTH_SOCK".to_string(), sock.display().to_string()));
+    }
+
+    if forward_gitconfig {
+        let home = env::var("HOME").context("HOME is not set; required for --forward-gitconfig")?;
+        let gitconfig = Path::new(&home).join(".gitconfig");
+        if !gitconfig.exists() {
+            bail!(
+                "--forward-gitconfig was set but {} doesn't exist",
+                gitconfig.display()
+            );
+        }
+        args.push("-v".to_string());
+        args.push(format!("{}:/home/claude/.gitconfig:ro", gitconfig.display()));
+    }
+
+    for name in &profile.env {
+        match env::var(name) {
+            Ok(value) => {
+                args.push("-e".to_string());
+                args.push(name.clone());
+                command_env.push((name.clone(), value));
+            }
+            Err(_) => debug!(
+                "profile env passthrough '{}' not set in host environment",
+                name
+            ),
+        }
+    }
+    for (key, value) in config::load_dotenv(&sandbox_dir)? {
+        args.push("-e".to_string());
+        args.push(key.clone());
+        command_env.push((key, value));
+    }
+
+    args.push(image_ref);
 
     debug!(
-        "exec: container run {} (token redacted)",
+        "running: container run {} (token redacted)",
         args[1..].join(" ")
     );
 
-    let err = Command::new("container")
-        .args(&args)
-        .env("CLAUDE_CODE_OAUTH_TOKEN", token)
-        .exec();
+    let mut command = Command::new("container");
+    command.args(&args).env("CLAUDE_CODE_OAUTH_TOKEN", token);
+    for (key, value) in command_env {
+        command.env(key, value);
+    }
+
+    // `-it` allocates a real terminal for the interactive Claude Code
+    // session, so stdio is inherited rather than piped/line-processed.
+    let status = process::run(command, timeouts, Io::Inherited, |_, _| LineAction::Continue)?;
 
-    Err(anyhow::anyhow!(err).context("failed to exec container run"))
+    if !status.success() {
+        bail!("container run exited with {status}");
+    }
+    Ok(())
 }
 
 fn init_sandbox(sandbox_dir: &Path, force: bool) -> Result<()> {
@@ -158,7 +370,7 @@ fn init_sandbox(sandbox_dir: &Path, force: bool) -> Result<()> {
     Ok(())
 }
 
-fn cmd_build() -> Result<()> {
+fn cmd_build(timeouts: Timeouts) -> Result<()> {
     check_container_available()?;
     let cwd = env::current_dir().context("failed to get current directory")?;
     let sandbox_dir = cwd.join(SANDBOX_DIR);
@@ -181,17 +393,23 @@ fn cmd_build() -> Result<()> {
         DEFAULT_IMAGE_NAME, containerfile_str
     );
 
-    let status = Command::new("container")
-        .args([
-            "build",
-            "-t",
-            DEFAULT_IMAGE_NAME,
-            "-f",
-            containerfile_str,
-            sandbox_str,
-        ])
-        .status()
-        .context("failed to execute: container")?;
+    let mut command = Command::new("container");
+    command.args([
+        "build",
+        "-t",
+        DEFAULT_IMAGE_NAME,
+        "-f",
+        containerfile_str,
+        sandbox_str,
+    ]);
+
+    let status = process::run(command, timeouts, Io::Captured, |stream, line| {
+        match stream {
+            process::Stream::Stdout => println!("{line}"),
+            process::Stream::Stderr => eprintln!("{line}"),
+        }
+        LineAction::Continue
+    })?;
 
     if !status.success() {
         bail!("container build failed");
@@ -0,0 +1,128 @@
+//! Network isolation modes for the sandboxed container run.
+//!
+//! Following rustwide's sandbox model (networking disabled by default,
+//! selectively enabled), `claude-sandbox` defaults to `restricted`.
+//!
+//! The `container` CLI's `--network` flag takes a named network to attach
+//! to (or `none`) — there's no per-run host egress filter to delegate
+//! to, and `none` drops the container's network namespace entirely, so
+//! it can't even reach the host. So `restricted` mode keeps the
+//! container on its default (bridged) network, reachable to the host,
+//! and only forwards it `HTTP_PROXY`/`HTTPS_PROXY` pointed at
+//! [`crate::proxy`], a host-side allowlisting HTTP(S) proxy. That makes
+//! the `.claude-sandbox/network.toml` allowlist real for HTTP(S) traffic
+//! from anything that honors those variables (Claude Code's own tool
+//! calls do); it's not a packet-level firewall, so it can't stop a
+//! process that opens raw sockets directly. `none` remains the mode to
+//! reach for when no outbound access at all is required.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use log::debug;
+use serde::Deserialize;
+
+const NETWORK_CONFIG_FILE: &str = "network.toml";
+
+/// Network access mode for the sandboxed container.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum NetworkMode {
+    /// No outbound network access at all.
+    None,
+    /// Only hosts/ports listed in `.claude-sandbox/network.toml` are
+    /// reachable over HTTP(S), enforced by a host-side allowlisting
+    /// proxy (see [`crate::proxy`]). The container otherwise keeps its
+    /// default network so it can reach that proxy.
+    #[default]
+    Restricted,
+    /// Unrestricted outbound network access.
+    Full,
+}
+
+/// The parsed contents of `.claude-sandbox/network.toml`.
+#[derive(Debug, Default, Deserialize)]
+pub struct NetworkConfig {
+    /// `host:port` entries the allowlist proxy permits CONNECTs to.
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+/// Builds the `container run` arguments implementing `mode`.
+///
+/// Only `none` passes a `--network` flag: it's the one mode that drops
+/// the container's network namespace entirely. `restricted` needs the
+/// container to keep reaching the host (see [`crate::proxy::start`]), so
+/// it runs on the default network same as `full`; the allowlist is
+/// enforced by the caller wiring `HTTP_PROXY`/`HTTPS_PROXY` at the
+/// allowlisting proxy instead.
+pub fn container_run_args(mode: NetworkMode) -> Vec<String> {
+    match mode {
+        NetworkMode::None => vec!["--network".to_string(), "none".to_string()],
+        NetworkMode::Restricted | NetworkMode::Full => Vec::new(),
+    }
+}
+
+/// Loads the `restricted`-mode allowlist from `<sandbox_dir>/network.toml`.
+/// Returns an empty allowlist (allowing nothing) if the file doesn't exist.
+pub fn load_allowlist(sandbox_dir: &Path) -> Result<NetworkConfig> {
+    let path = sandbox_dir.join(NETWORK_CONFIG_FILE);
+    if !path.exists() {
+        debug!(
+            "no {} found in {}; restricted mode will allow no hosts",
+            NETWORK_CONFIG_FILE,
+            sandbox_dir.display()
+        );
+        return Ok(NetworkConfig::default());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    toml::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn none_mode_disables_networking() {
+        assert_eq!(
+            container_run_args(NetworkMode::None),
+            vec!["--network", "none"]
+        );
+    }
+
+    #[test]
+    fn full_mode_adds_no_args() {
+        assert!(container_run_args(NetworkMode::Full).is_empty());
+    }
+
+    #[test]
+    fn restricted_mode_keeps_default_network() {
+        assert!(container_run_args(NetworkMode::Restricted).is_empty());
+    }
+
+    #[test]
+    fn load_allowlist_without_config_allows_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_allowlist(dir.path()).unwrap();
+        assert!(config.allow.is_empty());
+    }
+
+    #[test]
+    fn load_allowlist_reads_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(NETWORK_CONFIG_FILE),
+            "allow = [\"api.anthropic.com:443\", \"github.com:443\"]\n",
+        )
+        .unwrap();
+        let config = load_allowlist(dir.path()).unwrap();
+        assert_eq!(
+            config.allow,
+            vec!["api.anthropic.com:443", "github.com:443"]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Running external commands with timeouts and live output processing.
+//!
+//! Borrows rustwide's `ProcessLinesActions`/inactivity-timeout approach:
+//! in `Io::Captured` mode, the child's stdout/stderr are streamed
+//! line-by-line through a callback (which can, e.g., detect a sentinel
+//! line and ask to stop early); in `Io::Inherited` mode the child keeps
+//! the parent's stdio directly (required for an interactive session, but
+//! means only the overall timeout can be enforced — there's no captured
+//! output to judge inactivity from).
+//!
+//! `Io::Captured` spawns the child into its own process group so the
+//! whole group can be torn down at once on Ctrl-C or a timeout. An
+//! interactive `Io::Inherited` child instead stays in our own process
+//! group: it's already the terminal's foreground group, so the child can
+//! read/write the tty and receive job-control signals (SIGINT, SIGTTIN,
+//! ...) directly, the same as any other foreground subprocess. Ctrl-C
+//! and the overall timeout then terminate just that child process rather
+//! than the group.
+
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use log::{debug, warn};
+
+/// Which stream a line came from.
+#[derive(Clone, Copy, Debug)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// What a line callback wants to happen next.
+pub enum LineAction {
+    Continue,
+    Terminate,
+}
+
+/// Overall and inactivity timeouts for [`run`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Timeouts {
+    pub overall: Option<Duration>,
+    pub inactivity: Option<Duration>,
+}
+
+/// How the child's stdio is wired up.
+pub enum Io {
+    /// Pipe stdout/stderr and stream lines through the `on_line` callback.
+    /// Supports both the overall and the inactivity timeout.
+    Captured,
+    /// Inherit the parent's stdio, so e.g. an interactive TUI can drive a
+    /// real terminal. The `on_line` callback is never invoked, and the
+    /// inactivity timeout is ignored (a warning is logged if one was set)
+    /// since there's no captured output to judge inactivity from.
+    Inherited,
+}
+
+/// Parses a clap argument like `"5m"` or `"30s"` into a [`Duration`].
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    humantime::parse_duration(s).map_err(|e| e.to_string())
+}
+
+enum Event {
+    Line(Stream, String),
+    Exited(std::io::Result<ExitStatus>),
+}
+
+/// Spawns `command` with `io` and enforces `timeouts` and Ctrl-C by
+/// terminating the child (its process group for `Io::Captured`, the
+/// child process itself for `Io::Inherited`; see the module docs).
+pub fn run(
+    mut command: Command,
+    timeouts: Timeouts,
+    io: Io,
+    on_line: impl FnMut(Stream, &str) -> LineAction,
+) -> Result<ExitStatus> {
+    use std::os::unix::process::CommandExt;
+
+    if let Io::Inherited = io {
+        if timeouts.inactivity.is_some() {
+            warn!("--inactivity-timeout has no effect on an interactive session; ignoring it");
+        }
+    }
+
+    if let Io::Captured = io {
+        command.process_group(0);
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+
+    let mut child = command.spawn().context("failed to spawn child process")?;
+    let child_pid = child.id() as i32;
+    // Negative targets `kill`/`kill(2)` at a process group, positive at a
+    // single process (see kill(1)) — `Io::Captured` put the child in its
+    // own group above, `Io::Inherited` left it in ours.
+    let kill_target = match io {
+        Io::Captured => -child_pid,
+        Io::Inherited => child_pid,
+    };
+
+    ctrlc::set_handler(move || {
+        warn!("received interrupt, terminating pid {kill_target}");
+        kill_target_pid(kill_target);
+    })
+    .context("failed to install Ctrl-C handler")?;
+
+    let (tx, rx) = mpsc::channel();
+    let effective_timeouts = match io {
+        Io::Captured => {
+            spawn_line_reader(child.stdout.take().expect("stdout was piped"), Stream::Stdout, tx.clone());
+            spawn_line_reader(child.stderr.take().expect("stderr was piped"), Stream::Stderr, tx.clone());
+            timeouts
+        }
+        Io::Inherited => Timeouts {
+            overall: timeouts.overall,
+            inactivity: None,
+        },
+    };
+    thread::spawn(move || {
+        let status = child.wait();
+        let _ = tx.send(Event::Exited(status));
+    });
+
+    wait_with_timeouts(&rx, effective_timeouts, kill_target, on_line)
+}
+
+fn wait_with_timeouts(
+    rx: &Receiver<Event>,
+    timeouts: Timeouts,
+    kill_target: i32,
+    mut on_line: impl FnMut(Stream, &str) -> LineAction,
+) -> Result<ExitStatus> {
+    let start = Instant::now();
+    let mut last_activity = start;
+
+    loop {
+        let wait_for = [
+            timeouts.overall.map(|d| d.saturating_sub(start.elapsed())),
+            timeouts
+                .inactivity
+                .map(|d| d.saturating_sub(last_activity.elapsed())),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(Duration::from_secs(3600));
+
+        match rx.recv_timeout(wait_for) {
+            Ok(Event::Line(stream, line)) => {
+                last_activity = Instant::now();
+                if matches!(on_line(stream, &line), LineAction::Terminate) {
+                    debug!("line callback requested early termination of pid {kill_target}");
+                    kill_target_pid(kill_target);
+                    return wait_for_exit(rx);
+                }
+            }
+            Ok(Event::Exited(status)) => {
+                return status.context("failed to wait on child process");
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some(overall) = timeouts.overall {
+                    if start.elapsed() >= overall {
+                        warn!("overall timeout of {overall:?} elapsed, terminating pid {kill_target}");
+                        kill_target_pid(kill_target);
+                        let _ = wait_for_exit(rx);
+                        bail!("timed out after {:?}", overall);
+                    }
+                }
+                if let Some(inactivity) = timeouts.inactivity {
+                    if last_activity.elapsed() >= inactivity {
+                        warn!("no output for {inactivity:?}, terminating pid {kill_target}");
+                        kill_target_pid(kill_target);
+                        let _ = wait_for_exit(rx);
+                        bail!("timed out: no output for {:?}", inactivity);
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                bail!("child process output streams closed unexpectedly");
+            }
+        }
+    }
+}
+
+fn wait_for_exit(rx: &Receiver<Event>) -> Result<ExitStatus> {
+    for event in rx {
+        if let Event::Exited(status) = event {
+            return status.context("failed to wait on child process");
+        }
+    }
+    bail!("child process exited without a status")
+}
+
+fn spawn_line_reader<R: Read + Send + 'static>(reader: R, stream: Stream, tx: Sender<Event>) {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            match line {
+                Ok(line) => {
+                    if tx.send(Event::Line(stream, line)).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+fn kill_target_pid(target: i32) {
+    let _ = Command::new("kill")
+        .args(["-TERM", &target.to_string()])
+        .status();
+}
@@ -0,0 +1,137 @@
+//! An allowlisting HTTP(S) forward proxy backing `--network restricted`.
+//!
+//! The `container` CLI has no per-run host egress allowlist, so
+//! `restricted` mode instead keeps the container on its default network
+//! and forwards its HTTP(S) traffic through this proxy via
+//! `HTTP_PROXY`/`HTTPS_PROXY`. The proxy binds the host's loopback
+//! interface only — the same address `host.containers.internal`-style
+//! gateway hostnames resolve to from inside the container — so it isn't
+//! reachable from the rest of the LAN. It only permits CONNECT tunnels
+//! to `host:port` pairs listed in `.claude-sandbox/network.toml` —
+//! anything else is refused, so the allowlist is actually enforced
+//! rather than just asserted.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use log::{debug, warn};
+
+/// A running allowlisting proxy. Dropping it stops it from accepting new
+/// connections; connections already in progress are left to finish.
+pub struct Proxy {
+    pub port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Proxy {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Starts a proxy on an ephemeral loopback-only port that only permits
+/// CONNECTs to `allowlist` entries (`host:port` strings).
+pub fn start(allowlist: Vec<String>) -> Result<Proxy> {
+    let listener =
+        TcpListener::bind("127.0.0.1:0").context("failed to bind allowlist proxy listener")?;
+    listener
+        .set_nonblocking(true)
+        .context("failed to configure allowlist proxy listener")?;
+    let port = listener
+        .local_addr()
+        .context("failed to read allowlist proxy listener address")?
+        .port();
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_handle = stop.clone();
+
+    thread::spawn(move || {
+        while !stop_handle.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((client, _)) => {
+                    let allowlist = allowlist.clone();
+                    thread::spawn(move || {
+                        if let Err(err) = handle_connection(client, &allowlist) {
+                            debug!("allowlist proxy connection error: {err}");
+                        }
+                    });
+                }
+                Err(ref err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(50));
+                }
+                Err(err) => {
+                    warn!("allowlist proxy accept error: {err}");
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(Proxy { port, stop })
+}
+
+fn handle_connection(mut client: TcpStream, allowlist: &[String]) -> Result<()> {
+    let mut buf = [0u8; 4096];
+    let n = client.read(&mut buf).context("failed to read proxy request")?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let target = parse_connect_target(&request).context("expected an HTTP CONNECT request")?;
+
+    if !allowlist.iter().any(|allowed| allowed == &target) {
+        let _ = client.write_all(b"HTTP/1.1 403 Forbidden\r\n\r\n");
+        bail!("blocked connection to disallowed target '{target}'");
+    }
+
+    let mut upstream = TcpStream::connect(&target)
+        .with_context(|| format!("failed to connect to allowed target '{target}'"))?;
+    client
+        .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+        .context("failed to acknowledge CONNECT")?;
+
+    let mut client_to_upstream = client.try_clone().context("failed to clone client socket")?;
+    let mut upstream_to_client = upstream.try_clone().context("failed to clone upstream socket")?;
+    let forward = thread::spawn(move || {
+        let _ = std::io::copy(&mut client_to_upstream, &mut upstream);
+    });
+    let _ = std::io::copy(&mut upstream_to_client, &mut client);
+    let _ = forward.join();
+    Ok(())
+}
+
+fn parse_connect_target(request: &str) -> Option<String> {
+    let first_line = request.lines().next()?;
+    let mut parts = first_line.split_whitespace();
+    if !parts.next()?.eq_ignore_ascii_case("CONNECT") {
+        return None;
+    }
+    parts.next().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_connect_target_extracts_host_port() {
+        let request = "CONNECT api.anthropic.com:443 HTTP/1.1\r\nHost: api.anthropic.com:443\r\n\r\n";
+        assert_eq!(
+            parse_connect_target(request),
+            Some("api.anthropic.com:443".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_connect_target_rejects_non_connect_methods() {
+        let request = "GET http://example.com/ HTTP/1.1\r\n\r\n";
+        assert_eq!(parse_connect_target(request), None);
+    }
+
+    #[test]
+    fn parse_connect_target_rejects_empty_request() {
+        assert_eq!(parse_connect_target(""), None);
+    }
+}
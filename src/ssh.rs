@@ -0,0 +1,51 @@
+//! Forwarding the host SSH agent socket into the container.
+//!
+//! Same idea as creddy's ssh-agent integration: expose the host agent to
+//! the sandboxed process via a bind mount, rather than copying private
+//! keys into the image.
+
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+/// Validates that `SSH_AUTH_SOCK` is set and points at a real unix socket,
+/// returning its path.
+pub fn agent_socket_path() -> Result<PathBuf> {
+    let path = std::env::var("SSH_AUTH_SOCK")
+        .context("--forward-ssh-agent requires SSH_AUTH_SOCK to be set (is an SSH agent running?)")?;
+    let path = PathBuf::from(path);
+    validate_socket(&path)?;
+    Ok(path)
+}
+
+fn validate_socket(path: &Path) -> Result<()> {
+    let metadata = std::fs::metadata(path)
+        .with_context(|| format!("SSH_AUTH_SOCK '{}' does not exist", path.display()))?;
+
+    if !metadata.file_type().is_socket() {
+        bail!("SSH_AUTH_SOCK '{}' is not a unix socket", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_socket_rejects_regular_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("not-a-socket");
+        std::fs::write(&path, b"nope").unwrap();
+        assert!(validate_socket(&path).is_err());
+    }
+
+    #[test]
+    fn validate_socket_accepts_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("agent.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        assert!(validate_socket(&path).is_ok());
+    }
+}